@@ -0,0 +1,67 @@
+// Benchmarks over a synthetic large dataset (10k pages, 50k cards).
+// The app is a single binary, so its source is compiled in as a module here.
+// Save a baseline with `cargo bench -- --save-baseline main` and compare later runs with `cargo bench -- --baseline main`.
+use criterion::{criterion_group, criterion_main};
+
+#[allow(dead_code, unused_imports, clippy::all)]
+mod app {
+    include!("../main.rs");
+
+    use criterion::Criterion;
+    use ratatui::backend::TestBackend;
+    use std::hint::black_box;
+
+    const WORDS: [&str; 8] = ["meeting", "notes", "project", "review", "budget", "design", "roadmap", "retro"];
+
+    // 20 notebooks x 25 sections x 20 pages of 40 lines each, plus 50k flashcards.
+    fn large_app() -> App {
+        let mut app = App::new();
+        app.notebooks.clear();
+        for n in 0..20 {
+            let mut notebook = Notebook::new(format!("Notebook {}", n));
+            for s in 0..25 {
+                let mut section = Section::new(format!("Section {}", s));
+                for p in 0..20 {
+                    let mut page = Page::new(format!("Page {}-{}-{}", n, s, p));
+                    page.content = (0..40).map(|l| format!("{} line {} about the {} plan, see https://example.com/{}/{}", WORDS[(n + s + p + l) % 8], l, WORDS[(p + l) % 8], n, l)).collect::<Vec<_>>().join("\n");
+                    page.extract_links_and_images();
+                    section.pages.push(page);
+                }
+                notebook.sections.push(section);
+            }
+            app.notebooks.push(notebook);
+        }
+        app.cards = (0..50_000).map(|i| Card::new(format!("What is {} number {}?", WORDS[i % 8], i), format!("Answer {}", i), CardType::Basic)).collect();
+        app.current_notebook_idx = 0;
+        app.current_section_idx = 0;
+        app.current_page_idx = 0;
+        app
+    }
+
+    pub fn benches(c: &mut Criterion) {
+        let mut app = large_app();
+
+        let encoded = encode_app_data(&AppData::from_app(&app)).expect("encode");
+        c.bench_function("save: encode 10k pages + 50k cards", |b| b.iter(|| encode_app_data(black_box(&AppData::from_app(&app))).expect("encode")));
+        c.bench_function("load: decode 10k pages + 50k cards", |b| b.iter(|| decode_app_data(black_box(encoded.clone())).expect("decode")));
+
+        app.global_search_query = "roadmap".to_string();
+        c.bench_function("global search rebuild", |b| b.iter(|| app.rebuild_global_search_results()));
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).expect("terminal");
+        c.bench_function("tree panel render", |b| b.iter(|| {
+            terminal.draw(|frame| draw_tree_panel(frame, &mut app, frame.size())).expect("draw");
+        }));
+
+        let text: Vec<String> = (0..500).map(|l| format!("Teh {} was reviewed at the {} meeting on line {}", WORDS[l % 8], WORDS[(l + 3) % 8], l)).collect();
+        app.textarea = TextArea::new(text);
+        c.bench_function("spell check 500 lines", |b| b.iter(|| app.run_spell_check()));
+    }
+}
+
+criterion_group! {
+    name = benches;
+    config = criterion::Criterion::default().sample_size(10);
+    targets = app::benches
+}
+criterion_main!(benches);
@@ -0,0 +1,36 @@
+// Flashcards as the data file stores them: every card type has to read back as itself.
+// The app is a single binary, so its source is compiled in as a module here.
+#[allow(dead_code, unused_imports, clippy::all)]
+mod app {
+    include!("../main.rs");
+
+    #[test]
+    fn saved_cards_keep_their_type() {
+        for card_type in [CardType::Basic, CardType::Cloze, CardType::MultipleChoice] {
+            let cards = vec![Card::new("front".to_string(), "back".to_string(), card_type.clone())];
+            let saved = bincode::serialize(&cards).unwrap();
+            let loaded: Vec<Card> = bincode::deserialize(&saved).expect("saved card must load");
+            assert_eq!(loaded[0].card_type, card_type);
+            assert_eq!(loaded[0].front, "front");
+        }
+    }
+
+    // Updating from a file that names no collection or type keeps the card's own
+    #[test]
+    fn updated_cards_keep_their_collection() {
+        let mut filed = Card::new("front".to_string(), "back".to_string(), CardType::Basic);
+        filed.collection = Some("Spanish".to_string());
+        let mut cards = vec![filed];
+        let mut import = CardImport::new(&cards, CardImportStrategy::Update);
+        import.merge(&mut cards, Card::new("front".to_string(), "back".to_string(), CardType::Basic), Some(CardType::Cloze));
+        assert_eq!(cards[0].collection.as_deref(), Some("Spanish"));
+        assert_eq!(cards[0].card_type, CardType::Cloze);
+
+        let mut moved = Card::new("front".to_string(), "back".to_string(), CardType::Basic);
+        moved.collection = Some("French".to_string());
+        import.merge(&mut cards, moved, None);
+        assert_eq!(cards[0].collection.as_deref(), Some("French"));
+        assert_eq!(cards[0].card_type, CardType::Cloze, "a file without types keeps the card's own");
+        assert_eq!(import.updated, 2);
+    }
+}
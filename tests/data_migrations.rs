@@ -0,0 +1,260 @@
+// Loading data files written by older layouts: each stored version has to upgrade to DATA_VERSION.
+// The app is a single binary, so its source is compiled in as a module here.
+#[allow(dead_code, unused_imports, clippy::all)]
+mod app {
+    include!("../main.rs");
+
+    // App::new reads the config, so keep it away from the real one.
+    fn isolate_data_dir() {
+        static ISOLATE: Once = Once::new();
+        ISOLATE.call_once(|| {
+            let dir = env::temp_dir().join(format!("mynotes-migration-tests-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).expect("scratch dir");
+            for var in ["XDG_DATA_HOME", "XDG_CONFIG_HOME", "XDG_CACHE_HOME", "XDG_STATE_HOME"] {
+                env::set_var(var, dir.join(var));
+            }
+        });
+    }
+
+    fn sample_data() -> AppData {
+        isolate_data_dir();
+        let mut app = App::new();
+        app.tasks.push(parse_task_editor_content("Title: Renew passport\nDue: 2026-03-01\n", None, NaiveDate::from_ymd_opt(2026, 1, 5).unwrap()));
+        app.scratchpad = "call the bank".to_string();
+        AppData::from_app(&app)
+    }
+
+    // Files saved by earlier versions, each holding one task "Renew passport" (Do, due 2026-03-01) and a
+    // scratchpad. New layouts get a fixture of their own; existing ones never change.
+    const V2_FILE: &[u8] = include_bytes!("fixtures/data-v2.bin");
+    const V3_FILE: &[u8] = include_bytes!("fixtures/data-v3.bin");
+    const V4_FILE: &[u8] = include_bytes!("fixtures/data-v4.bin");
+    const V5_FILE: &[u8] = include_bytes!("fixtures/data-v5.bin");
+    // Saved by the first release, before the scratchpad: a page with a link, the task, a journal and a
+    // mistake day, a habit, an expense, a meal, a kanban card in progress and a cloze flashcard.
+    const V1_FILE: &[u8] = include_bytes!("fixtures/data-v1.bin");
+
+    fn assert_sample(data: &AppData) {
+        assert_eq!(data.version, DATA_VERSION);
+        assert_eq!(data.tasks.len(), 1);
+        let task = &data.tasks[0];
+        assert_eq!(task.title, "Renew passport");
+        assert_eq!(task.matrix, TaskMatrix::Do);
+        assert_eq!(task.due_date, NaiveDate::from_ymd_opt(2026, 3, 1));
+        assert!(!task.matrix_pinned);
+        assert_eq!(data.scratchpad, "call the bank");
+        assert_eq!(data.current_journal_date, NaiveDate::from_ymd_opt(2026, 1, 5).unwrap());
+    }
+
+    #[test]
+    fn current_files_round_trip_with_their_version() {
+        let data = sample_data();
+        let encoded = encode_app_data(&data).unwrap();
+        assert!(encoded.starts_with(DATA_MAGIC));
+        let decoded = decode_app_data(encoded.clone()).unwrap().0;
+        assert_eq!(decoded.version, DATA_VERSION);
+        assert_eq!(encode_app_data(&decoded).unwrap(), encoded);
+    }
+
+    // The live file is split per list, and a save leaves the lists that did not change alone.
+    #[cfg(not(feature = "sqlite"))]
+    #[test]
+    fn saves_rewrite_only_changed_lists() {
+        isolate_data_dir();
+        let path = get_current_year_file().unwrap();
+        write_app_data(sample_data()).unwrap();
+        let data = read_app_data(&path).unwrap();
+        assert_eq!(data.tasks[0].title, "Renew passport");
+        assert_eq!(data.scratchpad, "call the bank");
+
+        let cards = collection_file(&path, "cards");
+        fs::write(&cards, b"left alone").unwrap();
+        let mut data = sample_data();
+        data.tasks[0].title = "Renew visa".to_string();
+        write_app_data(data).unwrap();
+        assert_eq!(fs::read(&cards).unwrap(), b"left alone");
+        assert_eq!(read_collection::<Task>(&path, "tasks").unwrap()[0].title, "Renew visa");
+
+        fs::remove_file(&cards).unwrap();
+        write_app_data(sample_data()).unwrap();
+        assert_eq!(read_app_data(&path).unwrap().tasks[0].title, "Renew passport");
+    }
+
+    #[test]
+    fn first_release_files_upgrade() {
+        let (data, version) = decode_app_data(V1_FILE.to_vec()).unwrap();
+        assert_eq!((version, data.version), (1, DATA_VERSION));
+        let page = &data.notebooks[0].sections[0].pages[0];
+        assert_eq!((data.notebooks[0].title.as_str(), data.notebooks[0].sections[0].title.as_str(), page.title.as_str()), ("Home", "Plans", "Trip"));
+        assert_eq!(page.content, "Trip\nPack the tent https://example.com/tents");
+        assert_eq!(page.links, vec!["https://example.com/tents".to_string()]);
+        assert!(!page.locked && !page.archived && page.body.is_none());
+        let task = &data.tasks[0];
+        assert_eq!((task.title.as_str(), task.description.as_str()), ("Renew passport", "at the post office"));
+        assert_eq!(task.matrix, TaskMatrix::Do);
+        assert_eq!(task.due_date, NaiveDate::from_ymd_opt(2026, 3, 1));
+        assert!(!task.matrix_pinned);
+        assert_eq!(data.journal_entries[0].content, "Quiet day");
+        assert_eq!(data.journal_entries[0].mood.as_deref(), Some("calm"));
+        assert_eq!(data.mistake_entries[0].content, "Missed the bus");
+        assert_eq!(data.habits[0].name, "Walk");
+        assert!(data.habits[0].marks.contains(&NaiveDate::from_ymd_opt(2025, 12, 21).unwrap()));
+        assert_eq!((data.finances[0].category.as_str(), data.finances[0].amount), ("Food", -42.5));
+        assert_eq!((data.calories[0].meal.as_str(), data.calories[0].calories), ("Lunch", 450));
+        assert_eq!((data.kanban_cards[0].title.as_str(), data.kanban_cards[0].column, data.kanban_cards[0].board), ("Paint the fence", 1, 0));
+        assert_eq!(data.cards[0].card_type, CardType::Cloze);
+        assert_eq!(data.cards[0].collection.as_deref(), Some("Geography"));
+        assert_eq!(data.kanban_boards.len(), 1);
+        assert!(data.scratchpad.is_empty() && data.trash.is_empty());
+        assert_eq!(data.current_journal_date, NaiveDate::from_ymd_opt(2025, 12, 21).unwrap());
+        assert!(data.view_mode == ViewMode::Planner);
+
+        let encoded = encode_app_data(&data).unwrap();
+        assert_eq!(decode_app_data(encoded).unwrap().0.tasks[0].title, "Renew passport");
+    }
+
+    // The first load in a newer layout copies the old file aside before anything rewrites it.
+    #[test]
+    fn upgrades_keep_a_backup() {
+        isolate_data_dir();
+        let dir = env::temp_dir().join(format!("mynotes-migration-tests-{}", std::process::id())).join("backup");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("2025.bin");
+        fs::write(&path, V1_FILE).unwrap();
+        let (data, version) = read_stored_app_data(&path).unwrap();
+        assert_eq!((version, data.tasks[0].title.as_str()), (1, "Renew passport"));
+        backup_before_upgrade(&path, version).unwrap();
+        assert_eq!(fs::read(dir.join("2025.bin.v1.bak")).unwrap(), V1_FILE);
+        fs::write(&path, b"not a data file").unwrap();
+        assert!(read_stored_app_data(&path).is_err());
+        backup_before_upgrade(&path, version).unwrap();
+        assert_eq!(fs::read(dir.join("2025.bin.v1.bak")).unwrap(), V1_FILE);
+    }
+
+    #[test]
+    fn compressed_v2_files_upgrade() {
+        assert!(V2_FILE.starts_with(DATA_MAGIC_V1));
+        assert_sample(&decode_app_data(V2_FILE.to_vec()).unwrap().0);
+    }
+
+    #[test]
+    fn v3_files_upgrade() {
+        assert_sample(&decode_app_data(V3_FILE.to_vec()).unwrap().0);
+    }
+
+    #[test]
+    fn v4_files_upgrade() {
+        let data = decode_app_data(V4_FILE.to_vec()).unwrap().0;
+        assert_sample(&data);
+        let pages: Vec<&Page> = data.notebooks.iter().flat_map(|n| &n.sections).flat_map(|s| &s.pages).collect();
+        assert!(!pages.is_empty());
+        assert!(pages.iter().all(|p| !p.locked && !p.archived));
+    }
+
+    #[test]
+    fn v5_files_upgrade() {
+        let data = decode_app_data(V5_FILE.to_vec()).unwrap().0;
+        assert_sample(&data);
+        assert!(data.trash.is_empty());
+    }
+
+    #[test]
+    fn upgraded_files_round_trip() {
+        let upgraded = decode_app_data(V3_FILE.to_vec()).unwrap().0;
+        let encoded = encode_app_data(&upgraded).unwrap();
+        assert_sample(&decode_app_data(encoded).unwrap().0);
+    }
+
+    #[test]
+    fn json_exports_round_trip() {
+        let mut app = sample_data().into_app();
+        app.notebooks[0].sections[0].pages[0].content = "page text".to_string();
+        let path = env::temp_dir().join(format!("mynotes-migration-tests-{}", std::process::id())).join("export.json");
+        export_data_json(&mut app, Some(path.clone())).unwrap();
+        let data = read_data_json(&path).unwrap();
+        assert_eq!(data.version, DATA_VERSION);
+        assert_eq!(data.tasks[0].title, "Renew passport");
+        assert_eq!(data.scratchpad, "call the bank");
+        assert_eq!(data.notebooks[0].sections[0].pages[0].content, "page text");
+
+        let newer = fs::read_to_string(&path).unwrap().replacen(&format!("\"version\": {}", DATA_VERSION), &format!("\"version\": {}", DATA_VERSION + 1), 1);
+        fs::write(&path, newer).unwrap();
+        let err = read_data_json(&path).err().expect("newer export must not import");
+        assert!(err.to_string().contains("update mynotes"), "{}", err);
+    }
+
+    #[test]
+    fn merged_files_add_only_what_is_missing() {
+        let mut old = sample_data().into_app();
+        old.notebooks[0].sections[0].pages[0].content = "last year's page".to_string();
+        old.journal_entries.push(JournalEntry { date: NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(), content: "new year's eve".to_string(), mood: None, private: false });
+        let path = env::temp_dir().join(format!("mynotes-migration-tests-{}", std::process::id())).join("last-year.json");
+        export_data_json(&mut old, Some(path.clone())).unwrap();
+
+        let mut app = sample_data().into_app();
+        let pages = |app: &App| app.notebooks.iter().flat_map(|n| &n.sections).map(|s| s.pages.len()).sum::<usize>();
+        let before = pages(&app);
+        merge_data_file(&mut app, path.to_str().unwrap()).unwrap();
+        assert_eq!(app.tasks.len(), 1);
+        assert_eq!(pages(&app), before + 1);
+        assert_eq!(app.journal_entries.iter().filter(|e| e.content == "new year's eve").count(), 1);
+
+        let summary = merge_data_file(&mut app, path.to_str().unwrap()).unwrap();
+        assert!(summary.contains("0 pages, 0 tasks"), "{}", summary);
+        assert_eq!(pages(&app), before + 1);
+    }
+
+    // A page body missing from the .pages file stops the merge rather than adding the page empty
+    #[test]
+    fn merge_stops_when_a_page_body_is_unreadable() {
+        let mut old = sample_data();
+        let page = &mut old.notebooks[0].sections[0].pages[0];
+        page.title = "Lost body".to_string();
+        page.content.clear();
+        page.body = Some(0x5eed);
+        let path = env::temp_dir().join(format!("mynotes-migration-tests-{}", std::process::id())).join("2019.bin");
+        fs::write(&path, encode_app_data(&old).unwrap()).unwrap();
+
+        let mut app = sample_data().into_app();
+        let err = merge_data_file(&mut app, path.to_str().unwrap()).err().expect("unreadable body must stop the merge");
+        assert!(err.to_string().contains("Lost body"), "{}", err);
+        assert!(!app.notebooks.iter().flat_map(|n| &n.sections).flat_map(|s| &s.pages).any(|p| p.title == "Lost body"));
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn sealed_files_need_the_passphrase() {
+        let encoded = encode_app_data(&sample_data()).unwrap();
+        set_new_passphrase("correct horse").unwrap();
+        ENCRYPT_WRITES.store(true, AtomicOrdering::Relaxed);
+        let sealed = seal(encoded.clone());
+        ENCRYPT_WRITES.store(false, AtomicOrdering::Relaxed);
+        let sealed = sealed.unwrap();
+        assert!(sealed.starts_with(SEALED_MAGIC));
+        assert!(!sealed.windows(b"call the bank".len()).any(|w| w == b"call the bank"));
+        assert_eq!(decode_app_data(sealed.clone()).unwrap().0.scratchpad, "call the bank");
+
+        assert_eq!(unlock_with("wrong horse", &sealed).err().expect("wrong passphrase").to_string(), "Wrong passphrase");
+        assert!(passphrase_matches("correct horse"));
+        unlock_with("correct horse", &sealed).unwrap();
+    }
+
+    #[cfg(not(feature = "encryption"))]
+    #[test]
+    fn sealed_files_are_refused_without_the_feature() {
+        let mut sealed = SEALED_MAGIC.to_vec();
+        sealed.extend([0u8; 64]);
+        let err = decode_app_data(sealed).err().expect("sealed data must not load");
+        assert!(err.to_string().contains("--features encryption"), "{}", err);
+    }
+
+    #[test]
+    fn newer_files_are_refused() {
+        let mut data = sample_data();
+        data.version = DATA_VERSION + 1;
+        let err = decode_app_data(encode_app_data(&data).unwrap()).err().expect("newer layout must not load");
+        assert!(err.to_string().contains("update mynotes"), "{}", err);
+        assert!(migrate_payload(Vec::new(), 0).is_err());
+    }
+}
@@ -0,0 +1,713 @@
+// End-to-end UI flows driven through ratatui's TestBackend with synthetic key and mouse events.
+// The app is a single binary, so its source is compiled in as a module here.
+#[allow(dead_code, unused_imports, clippy::all)]
+mod app {
+    include!("../main.rs");
+
+    use crossterm::event::KeyEventState;
+    use ratatui::backend::TestBackend;
+
+    // This run's scratch folder, emptied the first time the harness starts.
+    fn scratch_dir() -> PathBuf {
+        env::temp_dir().join(format!("mynotes-ui-tests-{}", std::process::id()))
+    }
+
+    // Saves run for real, so point the data, config, cache and log dirs at a scratch folder before anything touches them.
+    fn isolate_data_dir() {
+        static ISOLATE: Once = Once::new();
+        ISOLATE.call_once(|| {
+            let dir = scratch_dir();
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).expect("scratch dir");
+            for var in ["XDG_DATA_HOME", "XDG_CONFIG_HOME", "XDG_CACHE_HOME", "XDG_STATE_HOME"] {
+                env::set_var(var, dir.join(var));
+            }
+        });
+    }
+
+    struct Harness {
+        app: App,
+        terminal: Terminal<TestBackend>,
+    }
+
+    impl Harness {
+        fn new() -> Self {
+            isolate_data_dir();
+            let mut harness = Self { app: App::new(), terminal: Terminal::new(TestBackend::new(160, 48)).expect("terminal") };
+            harness.draw();
+            harness
+        }
+
+        // Drawing records the button and list rects that mouse handling hit-tests against.
+        fn draw(&mut self) {
+            let app = &mut self.app;
+            self.terminal.draw(|frame| draw(frame, app)).expect("draw");
+        }
+
+        fn key_with(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+            let quit = handle_key(&mut self.app, KeyEvent { code, modifiers, kind: KeyEventKind::Press, state: KeyEventState::NONE }).expect("key");
+            assert!(!quit, "{:?} unexpectedly quit", code);
+            self.draw();
+        }
+
+        fn key(&mut self, code: KeyCode) {
+            self.key_with(code, KeyModifiers::NONE);
+        }
+
+        fn ctrl(&mut self, c: char) {
+            self.key_with(KeyCode::Char(c), KeyModifiers::CONTROL);
+        }
+
+        fn type_text(&mut self, text: &str) {
+            for c in text.chars() {
+                self.key(KeyCode::Char(c));
+            }
+        }
+
+        // Editors open with the cursor on the last template line; walk up to the "Title:" line.
+        fn end_of_first_line(&mut self) {
+            while self.app.textarea.cursor().0 > 0 {
+                self.key(KeyCode::Up);
+            }
+            self.key(KeyCode::End);
+        }
+
+        fn click_with(&mut self, button: MouseButton, rect: Rect) {
+            assert!(rect.width > 0 && rect.height > 0, "target was never drawn");
+            handle_mouse(&mut self.app, MouseEvent { kind: MouseEventKind::Down(button), column: rect.x + rect.width / 2, row: rect.y + rect.height / 2, modifiers: KeyModifiers::NONE });
+            self.draw();
+        }
+
+        fn click(&mut self, rect: Rect) {
+            self.click_with(MouseButton::Left, rect);
+        }
+
+        fn screen(&self) -> String {
+            let buffer = self.terminal.backend().buffer();
+            buffer.content.chunks(buffer.area.width as usize).map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>()).collect::<Vec<_>>().join("\n")
+        }
+
+        fn task_rect(&self, idx: usize) -> Rect {
+            self.app.task_items.iter().find(|(i, _)| *i == idx).map(|(_, rect)| *rect).expect("task row drawn")
+        }
+    }
+
+    #[test]
+    fn task_create_edit_complete_delete() {
+        let mut h = Harness::new();
+        h.app.tasks.clear();
+        h.key(KeyCode::F(2));
+        assert!(h.app.view_mode == ViewMode::Planner);
+
+        h.click(h.app.add_task_btn);
+        assert!(matches!(h.app.edit_target, EditTarget::TaskTitle));
+        h.end_of_first_line();
+        h.type_text("Buy milk");
+        h.ctrl('s');
+        assert!(!h.app.is_editing());
+        assert_eq!(h.app.tasks.len(), 1);
+        assert_eq!(h.app.tasks[0].title, "Buy milk");
+        assert!(h.screen().contains("Buy milk"));
+
+        h.click(h.app.edit_task_btn);
+        assert!(matches!(h.app.edit_target, EditTarget::TaskDetails));
+        h.end_of_first_line();
+        h.type_text(" and eggs");
+        h.ctrl('s');
+        assert_eq!(h.app.tasks[0].title, "Buy milk and eggs");
+        assert!(h.screen().contains("Buy milk and eggs"));
+
+        h.click_with(MouseButton::Middle, h.task_rect(0));
+        assert!(h.app.tasks[0].completed);
+
+        h.click(h.app.delete_task_btn);
+        assert!(h.app.tasks.is_empty());
+        assert!(!h.screen().contains("Buy milk"));
+    }
+
+    #[test]
+    fn import_cards_then_review() {
+        let mut h = Harness::new();
+        h.app.cards.clear();
+        let path = env::temp_dir().join(format!("mynotes-ui-cards-{}.json", std::process::id()));
+        fs::write(&path, r#"[{"front": "Capital of France?", "back": "Paris"}, {"front": "2 + 2", "back": "4", "card_type": "basic"}]"#).expect("card file");
+
+        h.key(KeyCode::F(8));
+        assert!(h.app.view_mode == ViewMode::Flashcards);
+        h.click(h.app.import_card_btn);
+        assert!(h.app.show_card_import_help);
+        h.click(h.app.card_import_edit_btn);
+        h.type_text(&path.to_string_lossy());
+        h.ctrl('s');
+        assert_eq!(h.app.pending_card_import_path.as_deref(), Some(path.to_string_lossy().as_ref()));
+        h.click(h.app.card_import_help_btn);
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(h.app.cards.len(), 2, "{}", h.app.validation_error_message);
+        assert!(h.app.show_success_popup);
+        h.key(KeyCode::Esc);
+        assert!(h.screen().contains("Capital of France?"));
+
+        h.key(KeyCode::Up);
+        h.key(KeyCode::Enter);
+        assert!(h.app.card_review_mode);
+        let reviewed = h.app.current_card_idx;
+        h.key(KeyCode::Char(' '));
+        assert!(h.app.show_card_answer);
+        h.key(KeyCode::Char('4'));
+        assert!(!h.app.show_card_answer);
+        assert_eq!(h.app.cards[reviewed].repetitions, 1);
+        assert!(h.app.cards[reviewed].next_review > today());
+        assert_eq!(h.app.cards[1 - reviewed].repetitions, 0);
+    }
+
+    #[test]
+    fn kanban_board_round_trips_through_markdown() {
+        let mut h = Harness::new();
+        h.app.kanban_cards.clear();
+        let mut report = KanbanCard::new("Write report".to_string(), "- [ ] outline\n\n- [x] draft".to_string());
+        report.due_date = NaiveDate::from_ymd_opt(2026, 11, 1);
+        report.matrix = TaskMatrix::Do;
+        h.app.kanban_cards.push(report);
+        h.app.kanban_cards.push(KanbanCard::new("Ship".to_string(), String::new()));
+        h.app.kanban_cards.push(KanbanCard::new("Drop me".to_string(), String::new()));
+        h.key(KeyCode::F(7));
+        assert!(h.app.view_mode == ViewMode::Kanban);
+
+        h.key(KeyCode::Char('x'));
+        h.key(KeyCode::Enter);
+        let path = get_export_dir().unwrap().join("board-main.md");
+        let md = fs::read_to_string(&path).expect("board file");
+        assert!(md.starts_with("# Main\n\n## To Do\n\n- [ ] Write report\n  Due: 2026-11-01\n  Matrix: Do\n  - [ ] outline\n\n  - [x] draft\n"), "{}", md);
+        h.key(KeyCode::Esc);
+
+        // Tick one card, swap another and add a column, as an outside editor would
+        let edited = md.replace("- [ ] Ship", "- [x] Ship").replace("- [ ] Drop me", "- [ ] Brand new").replace("## Done", "## Review\n\n## Done");
+        fs::write(&path, edited).unwrap();
+        h.key(KeyCode::Char('i'));
+        h.type_text(&path.to_string_lossy());
+        h.key(KeyCode::Enter);
+        assert!(h.app.show_success_popup, "{}", h.app.validation_error_message);
+        assert_eq!(h.app.kanban_boards[0].columns, ["To Do", "In Progress", "Review", "Done"]);
+        let titles: Vec<(&str, usize)> = h.app.kanban_cards.iter().map(|c| (c.title.as_str(), c.column)).collect();
+        assert_eq!(titles, [("Write report", 0), ("Ship", 3), ("Brand new", 0)]);
+        assert_eq!(h.app.kanban_cards[0].note, "- [ ] outline\n\n- [x] draft");
+        assert_eq!(h.app.kanban_cards[0].due_date, NaiveDate::from_ymd_opt(2026, 11, 1));
+        assert_eq!(h.app.trash.len(), 1);
+    }
+
+    #[test]
+    fn notebook_exports_as_a_markdown_folder() {
+        let mut h = Harness::new();
+        let mut section = Section::new("Plans: 2026".to_string());
+        for (title, content) in [("Trip", "pack the tent"), ("Trip", "second trip"), ("a/b", "")] {
+            let mut page = Page::new(title.to_string());
+            page.content = content.to_string();
+            page.modified_at = NaiveDate::from_ymd_opt(2026, 2, 3).unwrap();
+            section.pages.push(page);
+        }
+        let mut notebook = Notebook::new("Markdown Export".to_string());
+        notebook.sections.push(section);
+        h.app.notebooks = vec![notebook];
+        h.app.current_notebook_idx = 0;
+
+        h.key(KeyCode::Char('x'));
+        h.key(KeyCode::Tab);
+        assert!(h.screen().contains("Markdown folder"));
+        h.key(KeyCode::Enter);
+        assert!(h.app.show_success_popup, "{}", h.app.validation_error_message);
+        let dir = get_export_dir().unwrap().join("Markdown Export").join("Plans- 2026");
+        let trip = fs::read_to_string(dir.join("Trip.md")).expect("page file");
+        assert_eq!(trip, "---\ntitle: \"Trip\"\nmodified: 2026-02-03\n---\n\npack the tent\n");
+        assert!(fs::read_to_string(dir.join("Trip (2).md")).unwrap().ends_with("second trip\n"));
+        let modified: chrono::DateTime<Local> = fs::metadata(dir.join("a-b.md")).unwrap().modified().unwrap().into();
+        assert_eq!(modified.date_naive(), NaiveDate::from_ymd_opt(2026, 2, 3).unwrap());
+    }
+
+    #[test]
+    fn markdown_folder_imports_as_a_notebook() {
+        let mut h = Harness::new();
+        let vault = scratch_dir().join("vault").join("Vault");
+        fs::create_dir_all(vault.join(".obsidian")).unwrap();
+        // A link back up the tree must not be followed
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&vault, vault.join("Loop")).unwrap();
+        fs::create_dir_all(vault.join("Projects").join("2026")).unwrap();
+        fs::write(vault.join(".obsidian").join("hidden.md"), "not a page").unwrap();
+        fs::write(vault.join("Inbox.md"), "call the bank\r\n").unwrap();
+        fs::write(vault.join("Projects").join("2026").join("diagram.png"), b"png").unwrap();
+        fs::write(vault.join("Projects").join("2026").join("Plan.md"), "---\ntitle: \"Plan: v2\"\nmodified: 2026-02-03\ntags: [work]\n---\n\n![[diagram.png|300]]\n![](diagram.png)\n").unwrap();
+
+        h.key(KeyCode::Char('i'));
+        h.type_text(&vault.to_string_lossy());
+        h.key(KeyCode::Enter);
+        assert!(h.app.show_success_popup, "{}", h.app.validation_error_message);
+        assert!(h.app.success_message.contains("2 pages added"), "{}", h.app.success_message);
+        let notebook = h.app.notebooks.iter().find(|nb| nb.title == "Vault").expect("imported notebook");
+        let sections: Vec<&str> = notebook.sections.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(sections, ["Vault", "Projects / 2026"]);
+        assert_eq!(notebook.sections[0].pages[0].content, "call the bank");
+        let plan = &notebook.sections[1].pages[0];
+        assert_eq!(plan.title, "Plan: v2");
+        assert_eq!(plan.modified_at, NaiveDate::from_ymd_opt(2026, 2, 3).unwrap());
+        let image = vault.join("Projects").join("2026").join("diagram.png");
+        assert_eq!(plan.content, format!("---\ntags: [work]\n---\n\n![diagram.png]({0})\n![]({0})", image.display()));
+        assert_eq!(plan.images, [image.display().to_string()]);
+    }
+
+    // The command is written for sh
+    #[cfg(unix)]
+    #[test]
+    fn shell_command_output_is_appended_to_the_page() {
+        let mut h = Harness::new();
+        let mut section = Section::new("Ops".to_string());
+        let mut page = Page::new("Disk".to_string());
+        page.content = "Notes on the server".to_string();
+        section.pages.push(page);
+        let mut notebook = Notebook::new("Capture".to_string());
+        notebook.sections.push(section);
+        h.app.notebooks = vec![notebook];
+        select_page(&mut h.app, 0, 0, 0);
+
+        h.key(KeyCode::Char('!'));
+        h.type_text("echo used 42%; echo oops >&2; exit 3");
+        h.key(KeyCode::Enter);
+        finish_capture(&mut h);
+        assert!(h.app.show_success_popup, "{}", h.app.validation_error_message);
+        let content = &h.app.notebooks[0].sections[0].pages[0].content;
+        let (before, block) = content.split_once("\n\n`$ echo used 42%; echo oops >&2; exit 3` at ").expect("heading line");
+        assert_eq!(before, "Notes on the server");
+        assert!(block.ends_with(" (exit status 3)\n```\nused 42%\noops\n```"), "{}", block);
+        assert_eq!(h.app.last_capture_command, "echo used 42%; echo oops >&2; exit 3");
+    }
+
+    #[test]
+    fn ctrl_z_outside_editors_undoes_deletes_and_moves() {
+        let mut h = Harness::new();
+        h.app.tasks = ["First", "Second"].iter().map(|title| parse_task_editor_content(&format!("Title: {}\n", title), None, today())).collect();
+        h.app.cards = ["a", "b", "c"].iter().map(|front| Card::new(front.to_string(), "back".to_string(), CardType::Basic)).collect();
+        h.app.kanban_cards = vec![KanbanCard::new("Card".to_string(), String::new())];
+        h.app.trash.clear();
+
+        h.key(KeyCode::F(2));
+        h.click_with(MouseButton::Right, h.task_rect(0));
+        assert_eq!(h.app.tasks.len(), 1);
+        h.key(KeyCode::F(8));
+        h.app.current_card_idx = 0;
+        h.key_with(KeyCode::Down, KeyModifiers::SHIFT);
+        h.click(h.app.bulk_delete_btn);
+        assert_eq!(h.app.cards.len(), 1);
+        h.key(KeyCode::F(7));
+        h.key(KeyCode::Char('l'));
+        assert_eq!(h.app.kanban_cards[0].column, 1);
+        assert_eq!(h.app.trash.len(), 3);
+
+        h.ctrl('z');
+        assert_eq!(h.app.kanban_cards[0].column, 0);
+        h.ctrl('z');
+        assert!(h.app.view_mode == ViewMode::Flashcards);
+        assert_eq!(h.app.cards.iter().map(|c| c.front.as_str()).collect::<Vec<_>>(), ["a", "b", "c"]);
+        h.ctrl('z');
+        assert!(h.app.view_mode == ViewMode::Planner);
+        assert_eq!(h.app.tasks.iter().map(|t| t.title.as_str()).collect::<Vec<_>>(), ["First", "Second"]);
+        assert!(h.app.trash.is_empty());
+
+        h.ctrl('y');
+        assert_eq!(h.app.tasks.len(), 1);
+        assert_eq!(h.app.trash.len(), 1);
+        h.ctrl('z');
+        h.ctrl('z');
+        assert!(h.app.show_validation_error, "nothing left to undo");
+    }
+
+    // A deletion restored by hand from the trash has nothing left to undo, so there is nothing to redo either
+    #[test]
+    fn undo_of_a_deletion_already_restored_says_so() {
+        let mut h = Harness::new();
+        h.app.tasks = vec![parse_task_editor_content("Title: Only\n", None, today())];
+        h.app.trash.clear();
+        h.key(KeyCode::F(2));
+        h.click_with(MouseButton::Right, h.task_rect(0));
+        assert!(h.app.tasks.is_empty());
+        let item = h.app.trash.pop().expect("trashed task");
+        if let Trashed::Task(task) = item.item {
+            h.app.tasks.push(task);
+        }
+
+        h.ctrl('z');
+        assert!(h.app.show_validation_error);
+        assert!(h.app.validation_error_message.contains("Nothing was undone"), "{}", h.app.validation_error_message);
+        assert!(h.app.command_redo.is_empty());
+        assert_eq!(h.app.tasks.len(), 1);
+    }
+
+    // A currency sign in front of the numbers is several bytes long
+    #[test]
+    fn inline_calculator_handles_multibyte_text() {
+        let mut h = Harness::new();
+        h.key(KeyCode::F(3));
+        start_editing(&mut h.app, EditTarget::JournalEntry, String::new());
+        h.type_text("Coffee €3.50*2=");
+        h.key_with(KeyCode::Char('='), KeyModifiers::CONTROL);
+        assert_eq!(h.app.textarea.lines().join("\n"), "Coffee €3.50*2=7");
+        assert_eq!(last_number("Tea £2.40"), Some(2.4));
+        assert_eq!(last_number("Lunch €-12"), Some(-12.0));
+    }
+
+    // Offsets far past the calendar give no date instead of panicking
+    #[test]
+    fn huge_date_offsets_give_no_date() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let locale = Locale::resolve(&LocaleSettings::default());
+        assert_eq!(parse_natural_date("+3", today, &locale), NaiveDate::from_ymd_opt(2025, 3, 17));
+        for input in ["+999999999999999", "-9223372036854775807w", "in 9999999999 months", "9999999999999 years ago"] {
+            assert_eq!(parse_natural_date(input, today, &locale), None, "{}", input);
+        }
+        assert_eq!(parse_date_range("today to +99999999999999d", today, &locale), None);
+    }
+
+    #[test]
+    fn formula_with_a_huge_day_count_is_an_error() {
+        let h = Harness::new();
+        assert_eq!(eval_formula(&h.app, "count(tasks, days=7)"), Some(Ok("0".to_string())));
+        assert!(matches!(eval_formula(&h.app, "count(tasks, days=9999999999)"), Some(Err(_))));
+    }
+
+    // Markdown picked for a notebook export must not carry over to the next share popup
+    #[test]
+    fn share_popup_starts_without_markdown() {
+        let mut h = Harness::new();
+        assert!(h.app.view_mode == ViewMode::Notes);
+        h.key(KeyCode::Char('x'));
+        h.key(KeyCode::Tab);
+        assert!(h.app.share_markdown);
+        h.key(KeyCode::Esc);
+        h.key(KeyCode::Char('i'));
+        assert!(h.app.show_share && !h.app.share_markdown);
+        h.key(KeyCode::Enter);
+        assert!(!h.app.show_success_popup, "an empty import path is an error, not a Markdown export");
+    }
+
+    // The answer from the lookup thread only fills in a meal editor still on the same product
+    #[test]
+    fn food_lookup_answer_fills_the_open_meal() {
+        let mut h = Harness::new();
+        start_editing(&mut h.app, EditTarget::CaloriesNew, "Meal: oat bar\nCalories: \nNotes:\n".to_string());
+        let info = FoodInfo { name: "Oat Bar".to_string(), serving: "40 g".to_string(), kcal: 161.6, protein: Some(3.0), carbs: None, fat: None };
+        let answer = |info: &FoodInfo| Some(("oat bar".to_string(), std::sync::Arc::new(Mutex::new(Some(Ok(info.clone()))))));
+        h.app.food_lookup = answer(&info);
+        assert!(take_food_lookup(&mut h.app));
+        assert_eq!(h.app.textarea.lines()[..3], ["Meal: Oat Bar", "Calories: 162", "Notes:"]);
+        assert!(h.app.food_lookup.is_none());
+
+        h.app.food_lookup = answer(&info);
+        assert!(!take_food_lookup(&mut h.app), "the Meal: line changed since");
+    }
+
+    // A service that prints a lot before reading its input must not leave both sides waiting
+    #[cfg(unix)]
+    #[test]
+    fn paste_publishes_large_text_and_leaves_private_lines_out() {
+        let text = "note\n".repeat(100_000);
+        let command = "head -c 300000 /dev/zero | tr '\\0' a; echo; echo https://paste.test/abc; cat > /dev/null";
+        assert_eq!(publish_paste(command, &text).unwrap(), "https://paste.test/abc");
+
+        let mut h = Harness::new();
+        h.app.settings.paste_command = Some("cat".to_string());
+        open_paste(&mut h.app, "Page".to_string(), "public\nsecret #private\nmore".to_string());
+        assert_eq!(h.app.paste_pending.as_ref().map(|(_, text)| text.as_str()), Some("public\nmore"));
+    }
+
+    fn finish_capture(h: &mut Harness) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !take_capture_result(&mut h.app) {
+            assert!(Instant::now() < deadline, "capture never finished");
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    // The command runs off the UI thread; Esc stops it, and a backtick in it keeps the heading intact
+    #[cfg(unix)]
+    #[test]
+    fn captured_command_runs_in_the_background() {
+        let mut h = Harness::new();
+        assert!(h.app.current_page().is_some());
+        h.app.hierarchy_level = HierarchyLevel::Page;
+        let before = h.app.current_page().map(|p| p.content.clone()).unwrap_or_default();
+
+        capture_command_output(&mut h.app, "sleep 5");
+        h.key(KeyCode::Char('x'));
+        assert!(!h.app.show_share, "keys wait while the command runs");
+        h.key(KeyCode::Esc);
+        finish_capture(&mut h);
+        assert_eq!(h.app.current_page().map(|p| p.content.clone()).unwrap_or_default(), before);
+
+        capture_command_output(&mut h.app, "echo 'a`b'");
+        finish_capture(&mut h);
+        let content = h.app.current_page().map(|p| p.content.clone()).unwrap_or_default();
+        assert!(content.contains("`` $ echo 'a`b' `` at "), "{}", content);
+        assert!(content.trim_end().ends_with("```\na`b\n```"), "{}", content);
+    }
+
+    // Saving over a page whose text failed to load would keep the stored text and drop the change
+    #[test]
+    fn unreadable_page_refuses_changes() {
+        let mut h = Harness::new();
+        let mut section = Section::new("Ops".to_string());
+        let mut page = Page::new("Lost".to_string());
+        page.body = Some(0x1234);
+        section.pages.push(page);
+        let mut notebook = Notebook::new("Store".to_string());
+        notebook.sections.push(section);
+        h.app.notebooks = vec![notebook];
+        select_page(&mut h.app, 0, 0, 0);
+        h.app.hierarchy_level = HierarchyLevel::Page;
+
+        assert!(h.app.refuse_locked_page());
+        assert!(h.app.show_validation_error);
+        let shown = h.app.notebooks[0].sections[0].pages[0].content.clone();
+        append_to_page(&mut h.app, 0, 0, 0, "more");
+        assert_eq!(h.app.notebooks[0].sections[0].pages[0].content, shown);
+    }
+
+    #[test]
+    fn reminders_wait_for_the_editor_to_close() {
+        let mut h = Harness::new();
+        h.key(KeyCode::F(3));
+        start_editing(&mut h.app, EditTarget::JournalEntry, String::new());
+        h.app.due_reminders.push("Reminder: call".to_string());
+        h.type_text("hi");
+        h.key(KeyCode::Enter);
+        assert_eq!(h.app.textarea.lines().join("\n"), "hi\n");
+        assert!(!h.screen().contains("Reminder: call"));
+        h.key(KeyCode::Esc);
+        assert!(!h.app.is_editing());
+        assert!(h.screen().contains("Reminder: call"));
+    }
+
+    #[test]
+    fn merged_page_goes_to_the_trash_unless_locked() {
+        let mut h = Harness::new();
+        let mut section = Section::new("Ops".to_string());
+        for (title, text) in [("Keep", "kept"), ("Extra", "extra text")] {
+            let mut page = Page::new(title.to_string());
+            page.content = text.to_string();
+            section.pages.push(page);
+        }
+        let mut notebook = Notebook::new("Merge".to_string());
+        notebook.sections.push(section);
+        h.app.notebooks = vec![notebook];
+        h.app.trash.clear();
+        select_page(&mut h.app, 0, 0, 1);
+        h.app.hierarchy_level = HierarchyLevel::Page;
+
+        h.app.notebooks[0].sections[0].pages[1].locked = true;
+        merge_current_page_into(&mut h.app, 0, 0, 0);
+        assert_eq!(h.app.notebooks[0].sections[0].pages.len(), 2);
+        assert!(h.app.locked_page_notice);
+        h.app.locked_page_notice = false;
+
+        h.app.notebooks[0].sections[0].pages[1].locked = false;
+        merge_current_page_into(&mut h.app, 0, 0, 0);
+        assert_eq!(h.app.notebooks[0].sections[0].pages.len(), 1);
+        assert_eq!(h.app.notebooks[0].sections[0].pages[0].content, "kept\n\n## Extra\nextra text");
+        assert!(matches!(&h.app.trash[..], [TrashedItem { item: Trashed::Page { page, .. }, .. }] if page.content == "extra text"));
+    }
+
+    #[test]
+    fn list_filter_moves_the_selection_onto_a_shown_row() {
+        let mut h = Harness::new();
+        h.app.tasks = vec![Task::new("Buy milk".into(), String::new()), Task::new("Call mom".into(), String::new())];
+        h.key(KeyCode::F(2));
+        h.app.current_task_idx = 0;
+        h.key(KeyCode::Char('/'));
+        h.type_text("call");
+        assert_eq!(h.app.current_task_idx, 1);
+
+        // Nothing shown, nothing to delete
+        h.type_text("zzz");
+        h.click(h.app.delete_task_btn);
+        assert_eq!(h.app.tasks.len(), 2);
+    }
+
+    // Finance and Calories keep their entries when a filter hides every row
+    #[test]
+    fn delete_buttons_skip_entries_the_filter_hides() {
+        let mut h = Harness::new();
+        let day = h.app.current_journal_date;
+        h.app.finances = vec![parse_finance_editor_content("Category: Food\nAmount: 12\n", None, day).expect("expense")];
+        h.app.calories = vec![parse_calorie_editor_content("Meal: Apple\nCalories: 95\n", None, day).expect("meal")];
+        for view in [ViewMode::Finance, ViewMode::Calories] {
+            h.key(KeyCode::F(view.position() as u8 + 1));
+            assert!(h.app.view_mode == view);
+            h.key(KeyCode::Char('/'));
+            h.type_text("zzz");
+            h.click(if view == ViewMode::Finance { h.app.delete_fin_btn } else { h.app.delete_cal_btn });
+            h.key(KeyCode::Esc);
+        }
+        assert_eq!((h.app.finances.len(), h.app.calories.len()), (1, 1));
+    }
+
+    #[test]
+    fn repeated_finance_tags_are_dropped_wherever_they_appear() {
+        let entry = parse_finance_editor_content("Category: Food\nAmount: 12\nTags: #work, home, Work, home\n", None, today()).expect("entry");
+        assert_eq!(entry.tags, vec!["work".to_string(), "home".to_string()]);
+    }
+
+    #[test]
+    fn eating_window_can_run_past_midnight() {
+        let at = |t: &str| parse_clock_time(t).unwrap();
+        let window = FastingSettings { window_start: "18:00".into(), window_end: "02:00".into() }.window();
+        assert_eq!((window.start, window.end), (at("18:00"), at("02:00")));
+        assert!(window.covers(at("23:30")) && window.covers(at("01:30")) && !window.covers(at("12:00")));
+        assert!(window.holds(at("19:00"), at("23:00")));
+        assert!(!window.holds(at("01:00"), at("19:00")));
+    }
+
+    #[test]
+    fn ticking_a_checklist_item_keeps_the_line_endings() {
+        let mut note = "Steps\r\n- [x] one\r\n- [ ] two\r\n- [ ] three\n".to_string();
+        assert!(toggle_next_checklist_item(&mut note));
+        assert_eq!(note, "Steps\r\n- [x] one\r\n- [x] two\r\n- [ ] three\n");
+        assert!(toggle_next_checklist_item(&mut note));
+        assert!(!toggle_next_checklist_item(&mut note));
+        assert_eq!(note, "Steps\r\n- [x] one\r\n- [x] two\r\n- [x] three\n");
+    }
+
+    #[test]
+    fn too_small_terminal_takes_only_quit() {
+        let mut h = Harness::new();
+        h.app.tasks.clear();
+        h.terminal = Terminal::new(TestBackend::new(40, 10)).expect("terminal");
+        h.draw();
+        assert!(h.app.terminal_too_small);
+        h.key(KeyCode::F(2));
+        h.key(KeyCode::Char('n'));
+        assert!(h.app.view_mode == ViewMode::Notes && !h.app.is_editing());
+        assert!(handle_key(&mut h.app, KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)).expect("key"));
+    }
+
+    #[test]
+    fn paste_outside_a_text_input_is_ignored() {
+        let mut h = Harness::new();
+        h.app.tasks.clear();
+        assert!(!handle_paste(&mut h.app, "nq").expect("paste"));
+        assert!(h.app.view_mode == ViewMode::Notes && !h.app.is_editing());
+
+        h.key(KeyCode::F(2));
+        h.key(KeyCode::Char('/'));
+        handle_paste(&mut h.app, "milk").expect("paste");
+        assert_eq!(h.app.list_filter, "milk");
+    }
+
+    #[test]
+    fn imported_settings_with_commands_wait_for_a_yes() {
+        let mut h = Harness::new();
+        let mut settings = h.app.settings.clone();
+        settings.paste_command = Some("curl -sF 'file=@-' https://example.com".into());
+        let path = export_settings_bundle(&settings, Some(scratch_dir().join("settings-with-commands.toml"))).unwrap();
+
+        assert!(import_settings_bundle(&mut h.app, &path).unwrap().is_empty());
+        assert!(h.app.settings_import_pending.is_some());
+        h.draw();
+        assert!(h.screen().contains("paste_command = curl"));
+        h.key(KeyCode::Esc);
+        assert!(h.app.settings.paste_command.is_none());
+
+        import_settings_bundle(&mut h.app, &path).unwrap();
+        h.key(KeyCode::Enter);
+        assert!(h.app.settings_import_pending.is_none());
+        assert_eq!(h.app.settings.paste_command, settings.paste_command);
+    }
+
+    #[test]
+    fn summary_ranges_cover_at_most_ten_years() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let (from, to) = SummaryRange::Custom(date(1, 1, 1), date(2025, 6, 30)).bounds(date(2025, 6, 1), None);
+        assert_eq!((from, to), (date(2015, 7, 1), date(2025, 6, 30)));
+        assert_eq!(summary_months(from, to).len(), 120);
+        assert_eq!(cap_summary_range(date(2025, 1, 1), date(2025, 6, 30)), (date(2025, 1, 1), date(2025, 6, 30)));
+    }
+
+    // Each locale counts weeks and working days by its own settings, whichever was resolved last
+    #[test]
+    fn habit_weeks_follow_the_locale_passed_in() {
+        let monday = Locale::resolve(&LocaleSettings { week_start: Some("monday".to_string()), ..Default::default() });
+        let sunday = Locale::resolve(&LocaleSettings { week_start: Some("sunday".to_string()), working_days: Some(vec!["sun".to_string(), "mon".to_string()]), ..Default::default() });
+        let mut habit = Habit::new("Run".to_string());
+        habit.frequency = Recurrence::Weekly;
+        let (sun, mon) = (NaiveDate::from_ymd_opt(2025, 3, 9).unwrap(), NaiveDate::from_ymd_opt(2025, 3, 10).unwrap());
+        habit.marks.extend([sun, mon]);
+        assert_eq!(habit.run_ending(&monday, mon), 2);
+        assert_eq!(habit.run_ending(&sunday, mon), 1);
+        assert!(!is_working_day(&monday, sun));
+        assert!(is_working_day(&sunday, sun));
+    }
+
+    // Folding a group with nothing listed must not index into the empty result list
+    #[test]
+    fn folding_search_groups_with_no_results_does_nothing() {
+        let mut h = Harness::new();
+        h.ctrl('f');
+        assert!(h.app.show_global_search);
+        h.key(KeyCode::Left);
+        h.key(KeyCode::Right);
+        h.type_text("¤¤¤");
+        assert!(h.app.global_search_rows().is_empty());
+        h.key(KeyCode::Left);
+        h.key(KeyCode::Right);
+        assert!(h.app.show_global_search);
+    }
+
+    // A page edited this session still carries the hash it was loaded with; the report must see the new text
+    #[test]
+    fn change_report_lists_pages_edited_this_session() {
+        let mut h = Harness::new();
+        let page = &mut h.app.notebooks[0].sections[0].pages[0];
+        page.content = "first draft".to_string();
+        page.body = Some(body_hash(&page.content));
+        page.body_loaded = true;
+        let title = page.title.clone();
+        let mut saved = AppData::from_app(&h.app);
+        store_page_bodies(&mut saved).unwrap();
+        // As read back from disk, with nothing loaded
+        let saved = decode_app_data(encode_app_data(&saved).unwrap()).unwrap().0;
+        let day = today();
+
+        let report = changes_markdown(&saved, &AppData::from_app(&h.app), day, day, &h.app.locale);
+        assert!(!report.contains(&title), "{}", report);
+
+        h.app.notebooks[0].sections[0].pages[0].content = "second draft".to_string();
+        let report = changes_markdown(&saved, &data_as_of(&h.app, day).unwrap(), day, day, &h.app.locale);
+        assert!(report.contains("Edited") && report.contains(&title), "{}", report);
+    }
+
+    // Pages whose text is missing from the store are named in the review, not counted as empty
+    #[test]
+    fn year_review_names_pages_it_could_not_read() {
+        let mut h = Harness::new();
+        let page = &mut h.app.notebooks[0].sections[0].pages[0];
+        page.title = "Lost text".to_string();
+        page.content.clear();
+        page.body = Some(0x5eed);
+        page.body_loaded = false;
+        let year = data_year();
+        let (data, unreadable) = year_review_data(&mut h.app, year).unwrap();
+        assert_eq!(unreadable, vec!["Lost text".to_string()]);
+        let markdown = year_review_markdown(year, &data, &unreadable, &h.app.locale, false);
+        assert!(markdown.contains("could not be read") && markdown.contains("Lost text"), "{}", markdown);
+    }
+
+    #[test]
+    fn undo_restores_a_typed_word_in_one_step() {
+        let mut h = Harness::new();
+        h.key(KeyCode::F(3));
+        start_editing(&mut h.app, EditTarget::JournalEntry, String::new());
+        h.type_text("hello world");
+        assert_eq!(h.app.textarea.lines().join("\n"), "hello world");
+        h.ctrl('z');
+        assert_eq!(h.app.textarea.lines().join("\n"), "hello ");
+        h.ctrl('y');
+        assert_eq!(h.app.textarea.lines().join("\n"), "hello world");
+    }
+}